@@ -5,14 +5,48 @@ use colorized::*;
 use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::Client;
 use serde_json::{Value};
+use futures_util::StreamExt;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::clock::Slot;
+use solana_client::rpc_response::RpcVoteAccountStatus;
+use solana_ledger::leader_schedule::LeaderSchedule;
+use solana_sdk::clock::{Slot, NUM_CONSECUTIVE_LEADER_SLOTS};
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::epoch_schedule::EpochSchedule;
 use solana_sdk::pubkey::Pubkey;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::str::FromStr;
 use tokio::time::{sleep, Duration};
 
+/// A named cluster, resolving to its standard public RPC/WebSocket endpoints.
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum Cluster {
+    Mainnet,
+    Testnet,
+    Devnet,
+    Localnet,
+}
+
+impl Cluster {
+    fn rpc_url(&self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "https://api.mainnet-beta.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Localnet => "http://127.0.0.1:8899",
+        }
+    }
+
+    fn ws_url(&self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "wss://api.mainnet-beta.solana.com",
+            Cluster::Testnet => "wss://api.testnet.solana.com",
+            Cluster::Devnet => "wss://api.devnet.solana.com",
+            Cluster::Localnet => "ws://127.0.0.1:8900",
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 struct Args {
     /// The validator identity public key
@@ -21,6 +55,25 @@ struct Args {
     // The epoch provided to check on leader schedule for, default: current epoch
     #[clap(short, long)]
     epoch: Option<u64>,
+    /// Instead of scanning historical slots, subscribe to the live slot stream and
+    /// report this validator's leader performance as it happens.
+    #[clap(long)]
+    watch: bool,
+    /// How many slots ahead of the current slot to announce an upcoming leader block,
+    /// when running with `--watch`.
+    #[clap(long, default_value_t = 150)]
+    lead_window: u64,
+    /// Explicit RPC endpoint to use instead of the one resolved from `--cluster`.
+    #[clap(long)]
+    url: Option<String>,
+    /// Cluster to connect to when `--url` isn't given.
+    #[clap(long, value_enum, default_value_t = Cluster::Mainnet)]
+    cluster: Cluster,
+    /// Before running the schedule analysis, compare the inspected node's slot
+    /// height against the `--cluster` reference endpoint and report whether it's
+    /// caught up. Most useful when `--url` points at your own node.
+    #[clap(long)]
+    catchup: bool,
 }
 
 #[tokio::main]
@@ -32,8 +85,19 @@ async fn main() -> Result<()> {
     let _ = Pubkey::from_str(validator_pubkey_str)
         .map_err(|_| anyhow!("Invalid validator pubkey: {}", validator_pubkey_str))?;
 
-    let rpc_url = "https://api.mainnet-beta.solana.com";
-    let rpc = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::finalized());
+    let rpc_url = args.url.clone().unwrap_or_else(|| args.cluster.rpc_url().to_string());
+    let ws_url = args
+        .url
+        .as_ref()
+        .map(|url| url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1))
+        .unwrap_or_else(|| args.cluster.ws_url().to_string());
+    let rpc = RpcClient::new_with_commitment(rpc_url.clone(), CommitmentConfig::finalized());
+
+    if args.catchup {
+        let reference_rpc =
+            RpcClient::new_with_commitment(args.cluster.rpc_url().to_string(), CommitmentConfig::finalized());
+        run_catchup_check(&rpc, &reference_rpc).await?;
+    }
 
     // Get current epoch info
     let epoch_info = rpc
@@ -48,35 +112,63 @@ async fn main() -> Result<()> {
     // Get the current slot
     let current_slot = rpc.get_slot().await.context("Failed to get current slot")?;
 
-    // Calculate the first absolute slot of the current epoch
-    let epoch_first_slot = if epoch == current_epoch {
+    // Fetch the cluster's epoch schedule so epoch boundaries are exact, including
+    // during warmup where the naive "slots_per_epoch * epoch_delta" math is wrong.
+    let epoch_schedule = rpc
+        .get_epoch_schedule()
+        .await
+        .context("Failed to get epoch schedule")?;
+
+    if epoch == current_epoch {
         println!("Using current epoch: {}", current_epoch);
-        // For the current epoch, calculate based on `epoch_info`
-        epoch_info.absolute_slot - epoch_info.slot_index
     } else {
         println!("Using configured epoch: {}", epoch);
-        // For past or future epochs, calculate from epoch info
-        let slots_per_epoch = epoch_info.slots_in_epoch;
-        let slots_between_epochs = (epoch as i64 - current_epoch as i64) * slots_per_epoch as i64;
-        (epoch_info.absolute_slot as i64 - epoch_info.slot_index as i64 + slots_between_epochs)
-            .max(0) as u64 // Ensure non-negative
-    };
+    }
+
+    let epoch_first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+    let slots_in_epoch = epoch_schedule.get_slots_in_epoch(epoch);
+
+    // Fetch vote accounts once; it's needed both for the local-schedule fallback
+    // below and for the vote-performance summary, which is independent of whether
+    // the validator has any leader slots this epoch.
+    let vote_accounts_status = rpc
+        .get_vote_accounts()
+        .await
+        .context("Failed to get vote accounts")?;
+
+    // Surface the validator's own vote-performance alongside the skip analysis,
+    // since skip blame is more meaningful next to its voting health. This doesn't
+    // depend on leader-slot assignment, so report it even if the validator has no
+    // slots this epoch.
+    print_vote_performance_summary(&vote_accounts_status, &epoch_schedule, validator_pubkey_str, current_slot);
 
     // Retrieve the leader schedule for the current epoch
     let leader_schedule_raw = rpc
         .get_leader_schedule(Some(epoch_first_slot))
         .await
         .context("Failed to get leader schedule")?;
-    let leader_schedule_raw = leader_schedule_raw.ok_or_else(|| anyhow!("No leader schedule returned for epoch {}", epoch))?;
-
-    // Convert Vec<usize> to Vec<u64> if necessary
-    let leader_schedule: HashMap<String, Vec<u64>> = leader_schedule_raw
-        .into_iter()
-        .map(|(k, v)| {
-            let converted: Vec<u64> = v.into_iter().map(|x| x as u64).collect();
-            (k, converted)
-        })
-        .collect();
+
+    let leader_schedule: HashMap<String, Vec<u64>> = match leader_schedule_raw {
+        Some(raw) => {
+            // Convert Vec<usize> to Vec<u64> if necessary
+            raw.into_iter()
+                .map(|(k, v)| {
+                    let converted: Vec<u64> = v.into_iter().map(|x| x as u64).collect();
+                    (k, converted)
+                })
+                .collect()
+        }
+        None => {
+            // The cluster hasn't computed a schedule for this epoch yet (it's in the
+            // future). Reconstruct it locally from the current stake distribution so
+            // operators can still plan around it.
+            println!(
+                "No cluster-computed leader schedule for epoch {} yet; reconstructing locally from stake weights.",
+                epoch
+            );
+            compute_local_leader_schedule(&vote_accounts_status, epoch, slots_in_epoch)?
+        }
+    };
 
     // Check if our validator is in the leader schedule
     let our_slots = match leader_schedule.get(validator_pubkey_str) {
@@ -122,6 +214,12 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Instead of scanning historical slots, subscribe to the live slot stream and
+    // report leader performance as our slots finalize.
+    if args.watch {
+        return run_watch_mode(&rpc, &ws_url, blocks, validator_pubkey_str, args.lead_window).await;
+    }
+
     // Time estimation setup
     let current_unix_time = {
         let system_time = std::time::SystemTime::now()
@@ -143,48 +241,70 @@ async fn main() -> Result<()> {
         "[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
     ).unwrap());
 
+    // Fetch block-production and leader data for the assigned slots up to the current
+    // slot up front, in a handful of batched RPC calls, instead of one
+    // `get_blocks`/`get_slot_leaders` round-trip per slot.
+    let past_slots: Vec<Slot> = our_absolute_slots
+        .iter()
+        .copied()
+        .filter(|&slot| slot <= current_slot)
+        .collect();
+
+    let produced_slots: HashSet<Slot> = match (past_slots.iter().min(), past_slots.iter().max()) {
+        (Some(&min_slot), Some(&max_slot)) => fetch_produced_slots_in_range(&rpc, min_slot, max_slot).await?,
+        _ => HashSet::new(),
+    };
+
+    // Only the slots that were actually produced need a leader lookup; fetch them
+    // with a handful of ranged `get_slot_leaders` calls covering the produced slots,
+    // chunked to the RPC's ~5000-slot limit per call.
+    let mut produced_in_range: Vec<Slot> = past_slots
+        .iter()
+        .copied()
+        .filter(|slot| produced_slots.contains(slot))
+        .collect();
+    produced_in_range.sort_unstable();
+
+    let mut leader_by_slot: HashMap<Slot, Pubkey> = HashMap::new();
+    if let (Some(&first), Some(&last)) = (produced_in_range.first(), produced_in_range.last()) {
+        leader_by_slot = fetch_slot_leaders_in_range(&rpc, first, last).await?;
+    }
+
     for block in blocks {
         // We'll track if we printed anything about this block
         let mut non_produced_slots = Vec::new();
 
-        // First pass: check which slots are not produced by us
+        // First pass: check which slots are not produced by us, using the
+        // batch-fetched production/leader data instead of per-slot RPC calls.
         for &slot in &block {
             // Skip future slots
             if slot > current_slot {
                 continue;
             }
-            let produced = is_slot_produced(&rpc, slot).await?;
-            let mut non_produced = false;
-
-            if produced {
-                let leaders = rpc.get_slot_leaders(slot, 1).await?;
-                if let Some(final_leader) = leaders.get(0) {
-                    let final_leader_str = final_leader.to_string();
-                    if final_leader_str != *validator_pubkey_str {
-                        // Slot produced by someone else
-                        non_produced_slots.push((slot, Some(final_leader_str)));
-                        non_produced = true;
+
+            if produced_slots.contains(&slot) {
+                match leader_by_slot.get(&slot) {
+                    Some(final_leader) => {
+                        let final_leader_str = final_leader.to_string();
+                        if final_leader_str != *validator_pubkey_str {
+                            // Slot produced by someone else
+                            non_produced_slots.push((slot, Some(final_leader_str)));
+                        }
+                    }
+                    None => {
+                        // No leader info
+                        non_produced_slots.push((slot, None));
                     }
-                } else {
-                    // No leader info
-                    non_produced_slots.push((slot, None));
-                    non_produced = true;
                 }
             } else {
                 // Slot skipped
                 non_produced_slots.push((slot, None));
-                non_produced = true;
-            }
-
-            // Update the progress bar for every slot checked
-            pb.inc(1);
-
-            // small delay
-            if non_produced {
-                sleep(Duration::from_millis(20)).await;
             }
         }
 
+        // Advance the progress bar over this batch of slots.
+        pb.inc(block.len() as u64);
+
         // Only print block and leader info if we have non-produced slots
         if !non_produced_slots.is_empty() {
             let first_slot = block.first().unwrap();
@@ -305,16 +425,285 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-/// Check if a slot was produced by using `get_blocks`.
-/// If `get_blocks(slot, Some(slot))` returns a non-empty vector, a block exists at that slot.
-async fn is_slot_produced(rpc: &RpcClient, slot: Slot) -> Result<bool> {
-    let blocks = rpc.get_blocks(slot, Some(slot)).await?;
-    Ok(!blocks.is_empty())
-}
-
 /// Fetches all produced slots in the given range using `get_blocks`.
 /// Returns a HashSet of slots that have produced blocks.
 async fn fetch_produced_slots(rpc: &RpcClient, start_slot: Slot, end_slot: Slot) -> Result<HashSet<Slot>> {
     let confirmed_blocks = rpc.get_blocks(start_slot, Some(end_slot)).await?;
     Ok(confirmed_blocks.into_iter().collect())
-}
\ No newline at end of file
+}
+
+/// The largest slot range `get_blocks` will accept in a single RPC call.
+const MAX_GET_BLOCKS_RANGE: u64 = 500_000;
+
+/// Fetches all produced slots across `[min_slot, max_slot]`, chunking the range
+/// into pieces of at most `MAX_GET_BLOCKS_RANGE` slots so a validator with a wide
+/// assigned-slot span doesn't exceed the RPC's range limit.
+async fn fetch_produced_slots_in_range(rpc: &RpcClient, min_slot: Slot, max_slot: Slot) -> Result<HashSet<Slot>> {
+    let mut produced = HashSet::new();
+    let mut chunk_start = min_slot;
+    while chunk_start <= max_slot {
+        let chunk_end = (chunk_start + MAX_GET_BLOCKS_RANGE - 1).min(max_slot);
+        produced.extend(fetch_produced_slots(rpc, chunk_start, chunk_end).await?);
+        chunk_start = chunk_end + 1;
+    }
+    Ok(produced)
+}
+
+/// The largest slot count `get_slot_leaders` will accept in a single RPC call.
+const MAX_GET_SLOT_LEADERS_RANGE: u64 = 5_000;
+
+/// Fetches the leader for every slot across `[min_slot, max_slot]`, chunking the
+/// range into pieces of at most `MAX_GET_SLOT_LEADERS_RANGE` slots so a validator
+/// with assigned slots scattered across a whole epoch doesn't exceed the RPC's
+/// per-call limit.
+async fn fetch_slot_leaders_in_range(rpc: &RpcClient, min_slot: Slot, max_slot: Slot) -> Result<HashMap<Slot, Pubkey>> {
+    let mut leader_by_slot = HashMap::new();
+    let mut chunk_start = min_slot;
+    while chunk_start <= max_slot {
+        let chunk_end = (chunk_start + MAX_GET_SLOT_LEADERS_RANGE - 1).min(max_slot);
+        let leaders = rpc.get_slot_leaders(chunk_start, chunk_end - chunk_start + 1).await?;
+        for (offset, leader) in leaders.into_iter().enumerate() {
+            leader_by_slot.insert(chunk_start + offset as u64, leader);
+        }
+        chunk_start = chunk_end + 1;
+    }
+    Ok(leader_by_slot)
+}
+
+/// Reconstructs the leader schedule for `epoch` locally from the current stake
+/// distribution, mirroring the deterministic algorithm the cluster itself uses.
+/// This lets us inspect epochs the cluster hasn't computed a schedule for yet.
+fn compute_local_leader_schedule(
+    vote_accounts: &RpcVoteAccountStatus,
+    epoch: u64,
+    slots_in_epoch: u64,
+) -> Result<HashMap<String, Vec<u64>>> {
+    // Sum activated stake per validator identity across all of its vote accounts.
+    let mut stake_by_identity: HashMap<Pubkey, u64> = HashMap::new();
+    for vote_account in vote_accounts.current.iter().chain(vote_accounts.delinquent.iter()) {
+        let identity = Pubkey::from_str(&vote_account.node_pubkey)
+            .context("Vote account returned an invalid node pubkey")?;
+        *stake_by_identity.entry(identity).or_insert(0) += vote_account.activated_stake;
+    }
+
+    // The shuffle is seeded deterministically from the epoch, but the cluster still
+    // requires a stable input ordering: stake descending, ties broken by pubkey.
+    let mut stakes: Vec<(Pubkey, u64)> = stake_by_identity.into_iter().collect();
+    stakes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let schedule = LeaderSchedule::new(&stakes, epoch, slots_in_epoch, NUM_CONSECUTIVE_LEADER_SLOTS);
+
+    let mut leader_schedule: HashMap<String, Vec<u64>> = HashMap::new();
+    for (slot_index, leader) in schedule.get_slot_leaders().iter().enumerate() {
+        leader_schedule
+            .entry(leader.to_string())
+            .or_default()
+            .push(slot_index as u64);
+    }
+
+    Ok(leader_schedule)
+}
+/// Subscribes to the cluster's live slot stream and reports this validator's leader
+/// performance in real time: a heads-up before each upcoming block of our slots, and
+/// a produced/skipped verdict as each of our slots finalizes.
+async fn run_watch_mode(
+    rpc: &RpcClient,
+    wss_url: &str,
+    blocks: Vec<Vec<Slot>>,
+    validator_pubkey_str: &str,
+    lead_window: u64,
+) -> Result<()> {
+    let our_slots: HashSet<Slot> = blocks.iter().flatten().copied().collect();
+
+    println!(
+        "Watching live slots for validator {} ({} assigned slots, lead window: {} slots)...",
+        validator_pubkey_str,
+        our_slots.len(),
+        lead_window
+    );
+
+    let pubsub_client = PubsubClient::new(wss_url)
+        .await
+        .context("Failed to connect to the slot subscription websocket")?;
+    let (mut slot_stream, _unsubscribe) = pubsub_client
+        .slot_subscribe()
+        .await
+        .context("Failed to subscribe to slot updates")?;
+
+    let average_slot_duration = 0.4_f64;
+    let mut announced: HashSet<Slot> = HashSet::new();
+    let mut checked: HashSet<Slot> = HashSet::new();
+
+    while let Some(slot_info) = slot_stream.next().await {
+        let current_slot = slot_info.slot;
+        // `slot_info.slot` is only the live/processed tip; finality lags it by dozens
+        // of slots, so block-production checks must wait for `root` to pass a slot
+        // instead of the live slot, or they'll run before the block has landed.
+        let rooted_slot = slot_info.root;
+
+        // Heads-up for blocks of our leader slots that are now within the lead window.
+        for block in &blocks {
+            let Some(&first_slot) = block.first() else { continue };
+            if announced.contains(&first_slot) {
+                continue;
+            }
+            if first_slot >= current_slot && first_slot - current_slot <= lead_window {
+                let eta_secs = (first_slot - current_slot) as f64 * average_slot_duration;
+                println!(
+                    "Heads up: leader block {:?} starts in ~{:.1}s",
+                    block, eta_secs
+                );
+                announced.insert(first_slot);
+            }
+        }
+
+        // As our slots are rooted (finalized), check whether we actually produced them.
+        for &slot in &our_slots {
+            if slot > rooted_slot || checked.contains(&slot) {
+                continue;
+            }
+            checked.insert(slot);
+
+            let produced = fetch_produced_slots_in_range(rpc, slot, slot).await?.contains(&slot);
+            if !produced {
+                println!("Slot {}: skipped, not produced.", slot);
+                continue;
+            }
+
+            let leaders = rpc.get_slot_leaders(slot, 1).await?;
+            match leaders.first() {
+                Some(final_leader) if final_leader.to_string() == *validator_pubkey_str => {
+                    println!("Slot {}: produced by us.", slot);
+                }
+                Some(final_leader) => {
+                    println!("Slot {}: produced by {}, not us!", slot, final_leader);
+                }
+                None => {
+                    println!("Slot {}: no leader info available.", slot);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The standard delinquency distance: a validator whose last vote is more than
+/// this many slots behind the current slot is considered delinquent.
+const DELINQUENCY_THRESHOLD_SLOTS: u64 = 128;
+
+/// Prints a vote-credit/uptime summary for `validator_pubkey_str`'s vote account,
+/// so skip blame can be read alongside the validator's own voting health.
+fn print_vote_performance_summary(
+    vote_accounts_status: &RpcVoteAccountStatus,
+    epoch_schedule: &EpochSchedule,
+    validator_pubkey_str: &str,
+    current_slot: Slot,
+) {
+    let our_vote_account = vote_accounts_status
+        .current
+        .iter()
+        .chain(vote_accounts_status.delinquent.iter())
+        .find(|vote_account| vote_account.node_pubkey == *validator_pubkey_str);
+
+    let vote_account = match our_vote_account {
+        Some(vote_account) => vote_account,
+        None => {
+            println!(
+                "No vote account found for validator {}; skipping vote-performance summary.",
+                validator_pubkey_str
+            );
+            return;
+        }
+    };
+
+    let mut total_credits: u64 = 0;
+    let mut total_slots: u64 = 0;
+    for &(credit_epoch, credits, prev_credits) in &vote_account.epoch_credits {
+        total_credits += credits.saturating_sub(prev_credits);
+        total_slots += epoch_schedule.get_slots_in_epoch(credit_epoch);
+    }
+    let uptime_pct = if total_slots > 0 {
+        total_credits as f64 / total_slots as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let slots_behind = current_slot.saturating_sub(vote_account.last_vote);
+    let is_delinquent = slots_behind > DELINQUENCY_THRESHOLD_SLOTS;
+
+    println!("----------------------------------------");
+    println!(
+        "Vote performance over {} epochs: {} credits / {} slots ({:.2}% uptime)",
+        vote_account.epoch_credits.len(),
+        total_credits,
+        total_slots,
+        uptime_pct
+    );
+    if is_delinquent {
+        println!(
+            "{}: last vote at slot {}, {} slots behind current slot {}",
+            "DELINQUENT".color(Colors::BrightRedFg),
+            vote_account.last_vote,
+            slots_behind,
+            current_slot
+        );
+    } else {
+        println!(
+            "Last vote at slot {} ({} slots behind current)",
+            vote_account.last_vote, slots_behind
+        );
+    }
+}
+
+/// How many slot samples to take when checking catchup status.
+const CATCHUP_SAMPLE_COUNT: usize = 5;
+/// Delay between catchup samples.
+const CATCHUP_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Repeatedly samples `get_slot` from the inspected node and a reference cluster
+/// entrypoint, reporting how far behind/ahead the node is and whether the gap is
+/// closing, so an operator can confirm their validator is healthy before trusting
+/// the skip report.
+async fn run_catchup_check(node_rpc: &RpcClient, reference_rpc: &RpcClient) -> Result<()> {
+    println!("Checking catchup status against the reference cluster endpoint...");
+
+    let mut previous_gap: Option<i64> = None;
+    for sample in 1..=CATCHUP_SAMPLE_COUNT {
+        let node_slot = node_rpc
+            .get_slot()
+            .await
+            .context("Failed to get slot from the inspected node")?;
+        let reference_slot = reference_rpc
+            .get_slot()
+            .await
+            .context("Failed to get slot from the reference endpoint")?;
+        let gap = reference_slot as i64 - node_slot as i64;
+
+        let trend = match previous_gap {
+            Some(prev) if gap.abs() < prev.abs() => " (closing)",
+            Some(prev) if gap.abs() > prev.abs() => " (widening)",
+            Some(_) => " (stable)",
+            None => "",
+        };
+
+        match gap.cmp(&0) {
+            std::cmp::Ordering::Greater => {
+                println!("[{}/{}] Node is {} slots behind the reference endpoint{}", sample, CATCHUP_SAMPLE_COUNT, gap, trend)
+            }
+            std::cmp::Ordering::Less => {
+                println!("[{}/{}] Node is {} slots ahead of the reference endpoint{}", sample, CATCHUP_SAMPLE_COUNT, -gap, trend)
+            }
+            std::cmp::Ordering::Equal => {
+                println!("[{}/{}] Node is caught up with the reference endpoint", sample, CATCHUP_SAMPLE_COUNT)
+            }
+        }
+
+        previous_gap = Some(gap);
+        if sample < CATCHUP_SAMPLE_COUNT {
+            sleep(CATCHUP_SAMPLE_INTERVAL).await;
+        }
+    }
+
+    Ok(())
+}